@@ -1,18 +1,27 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt::{Display, Formatter};
 use std::fs;
+use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use base64::engine::general_purpose;
 use base64::Engine;
 use chromiumoxide::browser::{Browser, BrowserConfig};
+use chromiumoxide::cdp::browser_protocol::network::{
+    EnableParams as NetworkEnableParams, EventRequestWillBeSent,
+};
 use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
 use chromiumoxide::error::CdpError;
 use chromiumoxide::js::EvaluationResult;
 use chromiumoxide::Page;
 use futures::future::try_join_all;
 use futures::StreamExt;
+use lopdf::{Bookmark, Dictionary, Document, Object, ObjectId};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use tokio::sync::RwLock;
@@ -28,6 +37,7 @@ pub enum SimplePdfGeneratorError {
     BrowserError(String),
     IoError(String),
     PdfError(String),
+    SchemaMismatch(String),
 }
 
 impl Display for SimplePdfGeneratorError {
@@ -38,6 +48,9 @@ impl Display for SimplePdfGeneratorError {
             }
             SimplePdfGeneratorError::IoError(msg) => write!(f, "IO error: {}", msg),
             SimplePdfGeneratorError::PdfError(msg) => write!(f, "PDF error: {}", msg),
+            SimplePdfGeneratorError::SchemaMismatch(msg) => {
+                write!(f, "Schema mismatch: {}", msg)
+            }
         }
     }
 }
@@ -62,47 +75,449 @@ pub struct Asset {
     pub r#type: AssetType,
 }
 
+/// State handed to every [`Preprocessor`] while transforming a template's HTML.
+///
+/// Besides the [`Template`] being rendered, it collects the placeholder names
+/// whose elements must be hidden when their property is absent, so the
+/// xPath-based hiding pass can run after the pipeline.
+pub struct TemplateContext<'a> {
+    pub template: &'a Template,
+    xpath_texts: RefCell<Vec<String>>,
+}
+
+impl<'a> TemplateContext<'a> {
+    fn new(template: &'a Template) -> Self {
+        Self {
+            template,
+            xpath_texts: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Records an xPath predicate matching elements that should be hidden
+    /// because the property they reference resolved to nothing.
+    pub fn hide_if_empty(&self, xpath_text: String) {
+        self.xpath_texts.borrow_mut().push(xpath_text);
+    }
+
+    fn into_xpath_texts(self) -> Vec<String> {
+        self.xpath_texts.into_inner()
+    }
+}
+
+/// A transform applied to the template HTML before it is handed to Chromium.
+///
+/// Preprocessors run in registration order (see
+/// [`PrintOptions::preprocessors`]); each receives the output of the previous
+/// one, mirroring mdBook's preprocessor chain. Custom stages can expand
+/// Markdown, wrap `$...$` spans for client-side KaTeX, inline Mermaid, and so
+/// on.
+pub trait Preprocessor: Send + Sync {
+    fn process(
+        &self,
+        html: String,
+        ctx: &TemplateContext,
+    ) -> Result<String, SimplePdfGeneratorError>;
+}
+
+/// The default preprocessor: expands `%%token%%` placeholders from the
+/// template properties and inlines `<img>` sources as base64 data URIs.
+///
+/// It reproduces the library's original substitution behavior and runs first
+/// unless the caller replaces the pipeline, so the default output is unchanged.
+pub struct TokenAndImagePreprocessor;
+
+impl Preprocessor for TokenAndImagePreprocessor {
+    fn process(
+        &self,
+        html: String,
+        ctx: &TemplateContext,
+    ) -> Result<String, SimplePdfGeneratorError> {
+        let template = ctx.template;
+        let html = TOKENS_AND_IMAGES_REGEX
+            .replace_all(&html, |caps: &regex::Captures| {
+                let prop_name = caps.name("prop_name").map(|prop_name| prop_name.as_str());
+                let img_src = caps.name("img_src").map(|img_src| img_src.as_str());
+                let mut result = String::new();
+
+                if let Some(prop_name) = prop_name {
+                    if let Some(property) = template.properties.get(prop_name) {
+                        if property.is_none {
+                            ctx.hide_if_empty(format!("text() = '{}'", prop_name));
+                            result = prop_name.to_string();
+                        } else {
+                            result = html_escape::encode_text(&property.val).to_string()
+                        }
+                    }
+                } else if let Some(img_src) = img_src {
+                    if img_src.starts_with("data:image") {
+                        result = img_src.to_string();
+                    } else {
+                        let mime_type = mime_guess::from_path(img_src).first_raw();
+                        if let Some(mime_type) = mime_type {
+                            let mut img_src_path = Path::new(img_src).to_owned();
+                            if img_src_path.is_relative() {
+                                img_src_path = template
+                                    .html_path
+                                    .parent()
+                                    .unwrap_or_else(|| Path::new(""))
+                                    .join(img_src_path)
+                                    .canonicalize()
+                                    .unwrap_or_else(|_| PathBuf::new());
+                            }
+
+                            let img_data = fs::read(img_src_path).unwrap_or(Vec::new());
+                            let image_base64 = general_purpose::STANDARD.encode(img_data);
+                            let new_src = format!("data:{};base64,{}", mime_type, image_base64);
+                            result = caps.get(0).unwrap().as_str().replace(img_src, &new_src);
+                        } else {
+                            result = img_src.to_string();
+                        }
+                    }
+                }
+
+                result
+            })
+            .to_string();
+
+        Ok(html)
+    }
+}
+
+/// Measurement unit used to express page dimensions and margins.
+///
+/// Everything is converted to inches before being handed to CDP, so callers
+/// can pick whichever unit is most natural for their documents instead of
+/// having to pre-convert to millimetres.
+#[derive(Debug, Clone, Copy)]
+pub enum Unit {
+    Mm,
+    In,
+    Px,
+    Pt,
+}
+
+impl Unit {
+    /// How many of this unit make up a single inch.
+    fn per_inch(self) -> f64 {
+        match self {
+            Unit::Mm => 25.4,
+            Unit::In => 1.0,
+            Unit::Px => 96.0,
+            Unit::Pt => 72.0,
+        }
+    }
+}
+
+/// A distance together with the [`Unit`] it is expressed in.
+#[derive(Debug, Clone, Copy)]
+pub struct Length {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Length {
+    pub fn mm(value: f64) -> Self {
+        Self { value, unit: Unit::Mm }
+    }
+
+    pub fn inches(value: f64) -> Self {
+        Self { value, unit: Unit::In }
+    }
+
+    pub fn px(value: f64) -> Self {
+        Self { value, unit: Unit::Px }
+    }
+
+    pub fn pt(value: f64) -> Self {
+        Self { value, unit: Unit::Pt }
+    }
+
+    fn to_inches(self) -> f64 {
+        self.value / self.unit.per_inch()
+    }
+}
+
+/// A named paper size, or a [`Custom`](PaperSize::Custom) one expressed in any
+/// [`Unit`]. Named sizes resolve to their physical dimensions before the
+/// conversion to inches.
+#[derive(Debug, Clone, Copy)]
+pub enum PaperSize {
+    A3,
+    A4,
+    A5,
+    Letter,
+    Legal,
+    Tabloid,
+    Custom { width: Length, height: Length },
+}
+
+impl PaperSize {
+    /// The `(width, height)` of the page in inches.
+    fn dimensions_in(self) -> (f64, f64) {
+        match self {
+            PaperSize::A3 => (11.69, 16.54),
+            PaperSize::A4 => (8.27, 11.69),
+            PaperSize::A5 => (5.83, 8.27),
+            PaperSize::Letter => (8.5, 11.0),
+            PaperSize::Legal => (8.5, 14.0),
+            PaperSize::Tabloid => (11.0, 17.0),
+            PaperSize::Custom { width, height } => (width.to_inches(), height.to_inches()),
+        }
+    }
+}
+
+/// Condition that must hold before the page is captured to PDF.
+///
+/// Injected scripts and client-side renderers (KaTeX, Mermaid, web fonts, …)
+/// finish asynchronously, so printing immediately can clip late content. Every
+/// variant is bounded by [`PrintOptions::ready_timeout`], which maps to a
+/// [`PdfError`](SimplePdfGeneratorError::PdfError) on expiry.
+#[derive(Debug, Clone)]
+pub enum ReadyCondition {
+    /// Resolve once no new network request has started for `idle_ms`.
+    NetworkIdle { idle_ms: u64 },
+    /// Poll `document.querySelector` until the selector matches an element.
+    Selector(String),
+    /// Poll until the JavaScript expression evaluates truthy.
+    JsExpression(String),
+    /// Simply wait for the given duration.
+    Delay(Duration),
+}
+
+/// Document metadata written into the produced PDF's info dictionary, giving
+/// searchable, properly-attributed archival output.
+#[derive(Debug, Clone, Default)]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub subject: Option<String>,
+    pub keywords: Option<String>,
+    pub creator: Option<String>,
+}
+
+
+/// Pluggable backing store for the render cache. The default is an in-memory
+/// LRU ([`InMemoryLruCache`]); implement this trait to provide a disk or shared
+/// backend.
+pub trait RenderCacheStore: Send + Sync {
+    fn get(&self, key: &str) -> Option<Vec<u8>>;
+    fn insert(&self, key: String, value: Vec<u8>, ttl: Option<Duration>);
+}
+
+/// Opt-in content-addressed render cache handle stored on [`PrintOptions`].
+///
+/// Keys are a stable hash over the template properties, tables, HTML source,
+/// asset list and the print options, so any change to data, template or assets
+/// busts the entry. Files referenced from the HTML, such as `<img>` sources,
+/// are not part of the key, so editing one in place keeps the old entry.
+///
+/// Note that the [`preprocessors`](PrintOptions::preprocessors) pipeline is a
+/// `Box<dyn Preprocessor>` and cannot be hashed, so swapping or reconfiguring a
+/// preprocessor does *not* bust the key on its own. Callers that run a custom
+/// pipeline must bump [`pipeline_version`](Self::with_pipeline_version) whenever
+/// their preprocessors change, otherwise a stale render may be served.
+#[derive(Clone)]
+pub struct RenderCache {
+    store: Arc<dyn RenderCacheStore>,
+    ttl: Option<Duration>,
+    pipeline_version: Option<String>,
+}
+
+impl RenderCache {
+    /// A cache backed by an in-memory LRU holding at most `capacity` entries
+    /// (a capacity of zero is treated as one).
+    pub fn in_memory(capacity: usize) -> Self {
+        Self {
+            store: Arc::new(InMemoryLruCache::new(capacity)),
+            ttl: None,
+            pipeline_version: None,
+        }
+    }
+
+    /// A cache backed by a caller-supplied store (e.g. a disk backend).
+    pub fn with_store(store: Arc<dyn RenderCacheStore>) -> Self {
+        Self {
+            store,
+            ttl: None,
+            pipeline_version: None,
+        }
+    }
+
+    /// Sets the time-to-live applied to inserted entries.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Folds a caller-supplied preprocessor-pipeline identifier into the cache
+    /// key, so bumping it busts entries when the (unhashable) pipeline changes.
+    pub fn with_pipeline_version(mut self, version: impl Into<String>) -> Self {
+        self.pipeline_version = Some(version.into());
+        self
+    }
+}
+
+struct CacheEntry {
+    value: Vec<u8>,
+    expires_at: Option<Instant>,
+}
+
+struct LruInner {
+    entries: HashMap<String, CacheEntry>,
+    /// Keys ordered least- to most-recently used.
+    order: VecDeque<String>,
+}
+
+/// Capacity-bounded, least-recently-used in-memory [`RenderCacheStore`].
+pub struct InMemoryLruCache {
+    capacity: usize,
+    inner: Mutex<LruInner>,
+}
+
+impl InMemoryLruCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(LruInner {
+                entries: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }
+    }
+}
+
+impl RenderCacheStore for InMemoryLruCache {
+    fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let mut inner = self.inner.lock().unwrap();
+
+        if let Some(entry) = inner.entries.get(key) {
+            if entry.expires_at.is_some_and(|deadline| Instant::now() >= deadline) {
+                inner.entries.remove(key);
+                inner.order.retain(|k| k != key);
+                return None;
+            }
+        } else {
+            return None;
+        }
+
+        inner.order.retain(|k| k != key);
+        inner.order.push_back(key.to_string());
+        inner.entries.get(key).map(|entry| entry.value.clone())
+    }
+
+    fn insert(&self, key: String, value: Vec<u8>, ttl: Option<Duration>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let expires_at = ttl.map(|ttl| Instant::now() + ttl);
+        inner.order.retain(|k| k != &key);
+        inner.order.push_back(key.clone());
+        inner.entries.insert(key, CacheEntry { value, expires_at });
+
+        while inner.order.len() > self.capacity {
+            if let Some(evicted) = inner.order.pop_front() {
+                inner.entries.remove(&evicted);
+            }
+        }
+    }
+}
+
 pub struct PrintOptions {
     pub print_background: bool,
-    pub paper_width: Option<f64>,
-    pub paper_height: Option<f64>,
-    pub margin_top: Option<f64>,
-    pub margin_bottom: Option<f64>,
-    pub margin_left: Option<f64>,
-    pub margin_right: Option<f64>,
+    pub paper_size: Option<PaperSize>,
+    pub margin_top: Option<Length>,
+    pub margin_bottom: Option<Length>,
+    pub margin_left: Option<Length>,
+    pub margin_right: Option<Length>,
     pub page_ranges: Option<String>,
     pub prefer_css_page_size: bool,
     pub landscape: bool,
+    /// Whether to print the running header and footer defined by
+    /// [`header_template`](Self::header_template) and
+    /// [`footer_template`](Self::footer_template).
+    ///
+    /// Note that the page margins must be non-zero for the header/footer to be
+    /// visible: Chrome draws them inside the margin boxes, so a zero margin
+    /// leaves no room for them.
+    pub display_header_footer: bool,
+    /// HTML fragment used as the running header.
+    ///
+    /// Chrome substitutes the special classes `pageNumber`, `totalPages`,
+    /// `date`, `title` and `url`, e.g.
+    /// `<span class="pageNumber"></span> / <span class="totalPages"></span>`.
+    /// The template runs in an isolated context, so external CSS and assets
+    /// injected into the page are not applied to it.
+    pub header_template: Option<String>,
+    /// HTML fragment used as the running footer. See
+    /// [`header_template`](Self::header_template) for the available classes and
+    /// the isolation caveat.
+    pub footer_template: Option<String>,
+    /// Condition awaited after assets are injected and before the page is
+    /// captured. `None` prints as soon as the content is set.
+    pub ready_condition: Option<ReadyCondition>,
+    /// Overall budget for [`ready_condition`](Self::ready_condition). Expiry
+    /// surfaces as a [`PdfError`](SimplePdfGeneratorError::PdfError).
+    pub ready_timeout: Duration,
+    /// Ordered pipeline run over the template HTML before it reaches Chromium.
+    /// Defaults to a single [`TokenAndImagePreprocessor`]; callers can prepend
+    /// or append their own stages.
+    pub preprocessors: Vec<Box<dyn Preprocessor>>,
+    /// Metadata written into the rendered PDF's info dictionary. `None` leaves
+    /// Chrome's default metadata untouched.
+    ///
+    /// Embedding caller-supplied font files with a selectable base encoding is
+    /// intentionally unsupported: the generator captures whatever Chromium
+    /// renders, so glyphs are already drawn with the page's own (CSS-selected,
+    /// Chromium-subsetted) fonts. A post-hoc font object added here could not be
+    /// referenced by those existing content streams, so it would never affect
+    /// the output.
+    pub metadata: Option<PdfMetadata>,
+    /// Opt-in render cache. When set, identical renders are served from the
+    /// cache instead of re-running the headless pipeline. Only [`generate_pdf`]
+    /// and the batch and merge functions built on it consult the cache;
+    /// [`generate_pdf_from_html`] always renders.
+    pub cache: Option<RenderCache>,
 }
 
 impl Default for PrintOptions {
     fn default() -> Self {
         Self {
             print_background: true,
-            paper_width: None,
-            paper_height: None,
-            margin_top: Some(0.0),
-            margin_bottom: Some(0.0),
-            margin_left: Some(0.0),
-            margin_right: Some(0.0),
+            paper_size: None,
+            margin_top: Some(Length::mm(0.0)),
+            margin_bottom: Some(Length::mm(0.0)),
+            margin_left: Some(Length::mm(0.0)),
+            margin_right: Some(Length::mm(0.0)),
             page_ranges: None,
             prefer_css_page_size: false,
             landscape: false,
+            display_header_footer: false,
+            header_template: None,
+            footer_template: None,
+            ready_condition: None,
+            ready_timeout: Duration::from_secs(30),
+            preprocessors: vec![Box::new(TokenAndImagePreprocessor)],
+            metadata: None,
+            cache: None,
         }
     }
 }
 
 impl From<&PrintOptions> for PrintToPdfParams {
     fn from(val: &PrintOptions) -> Self {
+        let dimensions = val.paper_size.map(PaperSize::dimensions_in);
+
         PrintToPdfParams {
             print_background: Some(val.print_background),
-            paper_width: val.paper_width.map(|val| val / 25.4),
-            paper_height: val.paper_height.map(|val| val / 25.4),
-            margin_top: val.margin_top.map(|val| val / 25.4),
-            margin_bottom: val.margin_bottom.map(|val| val / 25.4),
-            margin_left: val.margin_left.map(|val| val / 25.4),
-            margin_right: val.margin_right.map(|val| val / 25.4),
+            paper_width: dimensions.map(|(width, _)| width),
+            paper_height: dimensions.map(|(_, height)| height),
+            margin_top: val.margin_top.map(Length::to_inches),
+            margin_bottom: val.margin_bottom.map(Length::to_inches),
+            margin_left: val.margin_left.map(Length::to_inches),
+            margin_right: val.margin_right.map(Length::to_inches),
             landscape: Some(val.landscape),
+            display_header_footer: Some(val.display_header_footer),
+            header_template: val.header_template.clone(),
+            footer_template: val.footer_template.clone(),
             ..Default::default()
         }
     }
@@ -113,18 +528,20 @@ struct ChromiumInstance {
 }
 
 impl ChromiumInstance {
-    async fn new() -> Self {
+    async fn new() -> Result<Self, SimplePdfGeneratorError> {
         let options = BrowserConfig::builder();
         let options = if NO_SANDBOX.load(Ordering::Relaxed) {
             options.no_sandbox()
         } else {
             options
         };
-        let options = options.build().expect("Invalid browser options.");
+        let options = options.build().map_err(|e| {
+            SimplePdfGeneratorError::BrowserError(format!("Invalid browser options: {}", e))
+        })?;
 
-        let (browser, mut handler) = Browser::launch(options)
-            .await
-            .expect("Couldn't create browser.");
+        let (browser, mut handler) = Browser::launch(options).await.map_err(|e| {
+            SimplePdfGeneratorError::BrowserError(format!("Couldn't create browser: {}", e))
+        })?;
 
         tokio::task::spawn(async move {
             while let Some(h) = handler.next().await {
@@ -139,7 +556,7 @@ impl ChromiumInstance {
             }
         });
 
-        ChromiumInstance { browser }
+        Ok(ChromiumInstance { browser })
     }
 }
 
@@ -149,6 +566,15 @@ static TOKENS_AND_IMAGES_REGEX: Lazy<Regex> = Lazy::new(|| {
 });
 static NO_SANDBOX: AtomicBool = AtomicBool::new(false);
 
+/// Maximum number of jobs [`generate_pdfs`] renders concurrently against the
+/// shared browser.
+const BATCH_CONCURRENCY: usize = 4;
+
+/// How often the [`Selector`](ReadyCondition::Selector) and
+/// [`JsExpression`](ReadyCondition::JsExpression) readiness conditions poll the
+/// page.
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 pub fn set_no_sandbox(val: bool) {
     NO_SANDBOX.store(val, Ordering::Relaxed);
 }
@@ -159,51 +585,12 @@ pub async fn generate_pdf_from_html(
     print_options: &PrintOptions,
 ) -> Result<Vec<u8>, SimplePdfGeneratorError> {
     let template = Template::default();
-    let mut xpath_texts: Vec<String> = Vec::new();
-    let html = TOKENS_AND_IMAGES_REGEX
-        .replace_all(&html, |caps: &regex::Captures| {
-            let prop_name = caps.name("prop_name").map(|prop_name| prop_name.as_str());
-            let img_src = caps.name("img_src").map(|img_src| img_src.as_str());
-            let mut result = String::new();
-
-            if let Some(prop_name) = prop_name {
-              if let Some(property) = template.properties.get(prop_name) {
-                  if property.is_none {
-                      xpath_texts.push(format!("text() = '{}'", prop_name));
-                      result = prop_name.to_string();
-                  } else {
-                      result = html_escape::encode_text(&property.val).to_string()
-                  }
-              }
-          } else if let Some(img_src) = img_src {
-              if img_src.starts_with("data:image") {
-                  result = img_src.to_string();
-              } else {
-                  let mime_type = mime_guess::from_path(img_src).first_raw();
-                  if let Some(mime_type) = mime_type {
-                      let mut img_src_path = Path::new(img_src).to_owned();
-                      if img_src_path.is_relative() {
-                          img_src_path = template
-                              .html_path
-                              .parent()
-                              .unwrap_or_else(|| Path::new(""))
-                              .join(img_src_path)
-                              .canonicalize()
-                              .unwrap_or_else(|_| PathBuf::new());
-                      }
-
-                      let img_data = fs::read(img_src_path).unwrap_or(Vec::new());
-                      let image_base64 = general_purpose::STANDARD.encode(img_data);
-                      let new_src = format!("data:{};base64,{}", mime_type, image_base64);
-                      result = caps.get(0).unwrap().as_str().replace(img_src, &new_src);
-                  } else {
-                      result = img_src.to_string();
-                  }
-              }
-          }
-          result
-      })
-      .to_string();
+    let ctx = TemplateContext::new(&template);
+    let mut html = html;
+    for preprocessor in &print_options.preprocessors {
+        html = preprocessor.process(html, &ctx)?;
+    }
+    let xpath_texts = ctx.into_xpath_texts();
 
     let browser = get_browser().await;
     let browser_instance = browser
@@ -277,9 +664,16 @@ pub async fn generate_pdf_from_html(
         })?;
     }
 
-    page.pdf(print_options.into())
+    if let Some(condition) = &print_options.ready_condition {
+        wait_for_ready(&page, condition, print_options.ready_timeout).await?;
+    }
+
+    let bytes = page
+        .pdf(print_options.into())
         .await
-        .map_err(|e| SimplePdfGeneratorError::PdfError(format!("Cannot create the pdf: {}", e)))
+        .map_err(|e| SimplePdfGeneratorError::PdfError(format!("Cannot create the pdf: {}", e)))?;
+
+    apply_pdf_metadata(bytes, print_options)
 }
 
 pub async fn generate_pdf(
@@ -293,53 +687,26 @@ pub async fn generate_pdf(
           SimplePdfGeneratorError::IoError(format!("Cannot read the html file: {}", e))
       })?;
 
-  let mut xpath_texts: Vec<String> = Vec::new();
-  let html = TOKENS_AND_IMAGES_REGEX
-      .replace_all(&html, |caps: &regex::Captures| {
-          let prop_name = caps.name("prop_name").map(|prop_name| prop_name.as_str());
-          let img_src = caps.name("img_src").map(|img_src| img_src.as_str());
-          let mut result = String::new();
-
-          if let Some(prop_name) = prop_name {
-              if let Some(property) = template.properties.get(prop_name) {
-                  if property.is_none {
-                      xpath_texts.push(format!("text() = '{}'", prop_name));
-                      result = prop_name.to_string();
-                  } else {
-                      result = html_escape::encode_text(&property.val).to_string()
-                  }
-              }
-          } else if let Some(img_src) = img_src {
-              if img_src.starts_with("data:image") {
-                  result = img_src.to_string();
-              } else {
-                  let mime_type = mime_guess::from_path(img_src).first_raw();
-                  if let Some(mime_type) = mime_type {
-                      let mut img_src_path = Path::new(img_src).to_owned();
-                      if img_src_path.is_relative() {
-                          img_src_path = template
-                              .html_path
-                              .parent()
-                              .unwrap_or_else(|| Path::new(""))
-                              .join(img_src_path)
-                              .canonicalize()
-                              .unwrap_or_else(|_| PathBuf::new());
-                      }
-
-                      let img_data = fs::read(img_src_path).unwrap_or(Vec::new());
-                      let image_base64 = general_purpose::STANDARD.encode(img_data);
-                      let new_src = format!("data:{};base64,{}", mime_type, image_base64);
-
-                      result = caps.get(0).unwrap().as_str().replace(img_src, &new_src);
-                  } else {
-                      result = img_src.to_string();
-                  }
-              }
-          }
+  let cache_entry = print_options.cache.as_ref().map(|cache| {
+      (
+          cache.clone(),
+          compute_cache_key(&template, &html, assets, print_options),
+      )
+  });
+  if let Some((cache, key)) = &cache_entry {
+      if let Some(cached) = cache.store.get(key) {
+          return Ok(cached);
+      }
+  }
 
-          result
-      })
-      .to_string();
+  let mut html = html;
+  let mut xpath_texts: Vec<String> = {
+      let ctx = TemplateContext::new(&template);
+      for preprocessor in &print_options.preprocessors {
+          html = preprocessor.process(html, &ctx)?;
+      }
+      ctx.into_xpath_texts()
+  };
 
   let browser = get_browser().await;
   let browser_instance = browser
@@ -434,9 +801,444 @@ pub async fn generate_pdf(
       })?;
   }
 
-  page.pdf(print_options.into())
+  if let Some(condition) = &print_options.ready_condition {
+      wait_for_ready(&page, condition, print_options.ready_timeout).await?;
+  }
+
+  let bytes = page
+      .pdf(print_options.into())
       .await
-      .map_err(|e| SimplePdfGeneratorError::PdfError(format!("Cannot create the pdf: {}", e)))
+      .map_err(|e| SimplePdfGeneratorError::PdfError(format!("Cannot create the pdf: {}", e)))?;
+
+  let result = apply_pdf_metadata(bytes, print_options)?;
+
+  if let Some((cache, key)) = cache_entry {
+      cache.store.insert(key, result.clone(), cache.ttl);
+  }
+
+  Ok(result)
+}
+
+/// Renders many templates concurrently against the single shared browser,
+/// aggregating per-document results instead of failing fast.
+///
+/// Each job is keyed by a caller-supplied `JobId` so successes and failures
+/// can be correlated back to their source. At most [`BATCH_CONCURRENCY`] jobs
+/// run at a time; the returned tuple holds the successfully rendered bytes and
+/// the per-job errors side by side, so one bad job can't abort the batch.
+pub async fn generate_pdfs<JobId>(
+    jobs: Vec<(JobId, Template)>,
+    assets: &[Asset],
+    print_options: &PrintOptions,
+) -> (
+    Vec<(JobId, Vec<u8>)>,
+    Vec<(JobId, SimplePdfGeneratorError)>,
+) {
+    let results = futures::stream::iter(jobs)
+        .map(|(id, template)| async move {
+            let result = generate_pdf(template, assets, print_options).await;
+            (id, result)
+        })
+        .buffer_unordered(BATCH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut successes = Vec::new();
+    let mut failures = Vec::new();
+    for (id, result) in results {
+        match result {
+            Ok(bytes) => successes.push((id, bytes)),
+            Err(e) => failures.push((id, e)),
+        }
+    }
+
+    (successes, failures)
+}
+
+/// Renders each section to PDF bytes and concatenates them into a single
+/// document, so multi-section reports end up in one file.
+///
+/// Sections are rendered exactly as [`generate_pdf`] would render them. When a
+/// section supplies a title, a top-level outline entry pointing at that
+/// section's first page is emitted, giving the merged report a clickable table
+/// of contents.
+pub async fn generate_merged_pdf(
+    sections: Vec<(Option<String>, Template)>,
+    assets: &[Asset],
+    print_options: &PrintOptions,
+) -> Result<Vec<u8>, SimplePdfGeneratorError> {
+    let mut documents = Vec::with_capacity(sections.len());
+    for (title, template) in sections {
+        let bytes = generate_pdf(template, assets, print_options).await?;
+        let document = Document::load_mem(&bytes).map_err(|e| {
+            SimplePdfGeneratorError::PdfError(format!("Cannot load the rendered section: {}", e))
+        })?;
+        documents.push((title, document));
+    }
+
+    let merged = merge_documents(documents)?;
+    apply_pdf_metadata(merged, print_options)
+}
+
+/// Merges already-rendered PDF documents into one, renumbering object trees,
+/// rebuilding the page tree and catalog, and attaching an outline built from
+/// the per-section bookmarks.
+fn merge_documents(
+    documents: Vec<(Option<String>, Document)>,
+) -> Result<Vec<u8>, SimplePdfGeneratorError> {
+    let mut max_id = 1;
+    let mut documents_pages = BTreeMap::new();
+    let mut documents_objects = BTreeMap::new();
+    let mut document = Document::with_version("1.5");
+
+    for (title, mut doc) in documents {
+        doc.renumber_objects_with(max_id);
+        max_id = doc.max_id + 1;
+
+        let mut first_page = true;
+        for object_id in doc.get_pages().into_values() {
+            if first_page {
+                if let Some(title) = &title {
+                    let bookmark = Bookmark::new(title.clone(), [0.0, 0.0, 0.0], 0, object_id);
+                    document.add_bookmark(bookmark, None);
+                }
+                first_page = false;
+            }
+
+            if let Ok(object) = doc.get_object(object_id) {
+                documents_pages.insert(object_id, object.to_owned());
+            }
+        }
+
+        documents_objects.extend(doc.objects);
+    }
+
+    let mut catalog_object: Option<(ObjectId, Object)> = None;
+    let mut pages_object: Option<(ObjectId, Object)> = None;
+
+    for (object_id, object) in &documents_objects {
+        match object.type_name().unwrap_or("") {
+            "Catalog" => {
+                catalog_object = Some((
+                    catalog_object.as_ref().map_or(*object_id, |(id, _)| *id),
+                    object.clone(),
+                ));
+            }
+            "Pages" => {
+                if let Ok(dictionary) = object.as_dict() {
+                    let mut dictionary = dictionary.clone();
+                    if let Some((_, old)) = &pages_object {
+                        if let Ok(old_dictionary) = old.as_dict() {
+                            dictionary.extend(old_dictionary);
+                        }
+                    }
+
+                    pages_object = Some((
+                        pages_object.as_ref().map_or(*object_id, |(id, _)| *id),
+                        Object::Dictionary(dictionary),
+                    ));
+                }
+            }
+            "Page" | "Outlines" | "Outline" => {}
+            _ => {
+                document.objects.insert(*object_id, object.clone());
+            }
+        }
+    }
+
+    let pages_object = pages_object.ok_or_else(|| {
+        SimplePdfGeneratorError::PdfError("No page tree found in the sections".to_string())
+    })?;
+    let catalog_object = catalog_object.ok_or_else(|| {
+        SimplePdfGeneratorError::PdfError("No catalog found in the sections".to_string())
+    })?;
+
+    for (object_id, object) in &documents_pages {
+        if let Ok(dictionary) = object.as_dict() {
+            let mut dictionary = dictionary.clone();
+            dictionary.set("Parent", pages_object.0);
+            document
+                .objects
+                .insert(*object_id, Object::Dictionary(dictionary));
+        }
+    }
+
+    if let Ok(dictionary) = pages_object.1.as_dict() {
+        let mut dictionary = dictionary.clone();
+        dictionary.set("Count", documents_pages.len() as u32);
+        dictionary.set(
+            "Kids",
+            documents_pages
+                .keys()
+                .map(|object_id| Object::Reference(*object_id))
+                .collect::<Vec<_>>(),
+        );
+        document
+            .objects
+            .insert(pages_object.0, Object::Dictionary(dictionary));
+    }
+
+    if let Ok(dictionary) = catalog_object.1.as_dict() {
+        let mut dictionary = dictionary.clone();
+        dictionary.set("Pages", pages_object.0);
+        dictionary.remove(b"Outlines");
+        document
+            .objects
+            .insert(catalog_object.0, Object::Dictionary(dictionary));
+    }
+
+    document.trailer.set("Root", catalog_object.0);
+    document.max_id = document.objects.len() as u32;
+    document.renumber_objects();
+    document.adjust_zero_pages();
+
+    if let Some(outline_id) = document.build_outline() {
+        if let Ok(Object::Dictionary(dictionary)) = document.get_object_mut(catalog_object.0) {
+            dictionary.set("Outlines", Object::Reference(outline_id));
+        }
+    }
+
+    document.compress();
+
+    let mut buffer = Vec::new();
+    document.save_to(&mut buffer).map_err(|e| {
+        SimplePdfGeneratorError::PdfError(format!("Cannot save the merged pdf: {}", e))
+    })?;
+
+    Ok(buffer)
+}
+
+/// Computes a stable content-addressed cache key over the HTML source, the
+/// template properties and tables, the asset list (paths and contents), the
+/// print options and the cache's pipeline version. Files referenced from the
+/// HTML, such as `<img>` sources, are not hashed.
+fn compute_cache_key(
+    template: &Template,
+    html: &str,
+    assets: &[Asset],
+    print_options: &PrintOptions,
+) -> String {
+    let mut hasher = DefaultHasher::new();
+    html.hash(&mut hasher);
+
+    let mut properties: Vec<_> = template.properties.iter().collect();
+    properties.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, property) in properties {
+        name.hash(&mut hasher);
+        property.val.hash(&mut hasher);
+        property.is_none.hash(&mut hasher);
+        property.is_tabledata.hash(&mut hasher);
+    }
+
+    let mut tables: Vec<_> = template.tables.iter().collect();
+    tables.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, data) in tables {
+        name.hash(&mut hasher);
+        data.hash(&mut hasher);
+    }
+
+    for asset in assets {
+        asset.path.to_string_lossy().hash(&mut hasher);
+        match asset.r#type {
+            AssetType::Style => 0u8,
+            AssetType::Script => 1u8,
+        }
+        .hash(&mut hasher);
+
+        // Hash the asset's contents so editing a CSS/JS file in place (same
+        // path) busts the key. If it can't be read, fall back to a marker so
+        // the miss is at least deterministic.
+        match fs::read(&asset.path) {
+            Ok(bytes) => bytes.hash(&mut hasher),
+            Err(_) => "<unreadable-asset>".hash(&mut hasher),
+        }
+    }
+
+    print_options_fingerprint(print_options).hash(&mut hasher);
+
+    // The preprocessor pipeline can't be hashed (it's `Box<dyn>`), so fold in
+    // the caller-supplied pipeline version when present.
+    if let Some(cache) = &print_options.cache {
+        cache.pipeline_version.hash(&mut hasher);
+    }
+
+    format!("{:016x}", hasher.finish())
+}
+
+/// A debug fingerprint of the render-affecting fields of [`PrintOptions`].
+fn print_options_fingerprint(print_options: &PrintOptions) -> String {
+    format!(
+        "{}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{}|{}|{}|{:?}|{:?}|{:?}|{:?}",
+        print_options.print_background,
+        print_options.paper_size,
+        print_options.margin_top,
+        print_options.margin_bottom,
+        print_options.margin_left,
+        print_options.margin_right,
+        print_options.page_ranges,
+        print_options.prefer_css_page_size,
+        print_options.landscape,
+        print_options.display_header_footer,
+        print_options.header_template,
+        print_options.footer_template,
+        print_options.ready_condition,
+        print_options.metadata,
+    )
+}
+
+/// Writes the requested metadata into the PDF's info dictionary, returning the
+/// original bytes untouched when no metadata is set.
+fn apply_pdf_metadata(
+    bytes: Vec<u8>,
+    print_options: &PrintOptions,
+) -> Result<Vec<u8>, SimplePdfGeneratorError> {
+    let Some(metadata) = &print_options.metadata else {
+        return Ok(bytes);
+    };
+
+    let mut document = Document::load_mem(&bytes).map_err(|e| {
+        SimplePdfGeneratorError::PdfError(format!("Cannot load the rendered pdf: {}", e))
+    })?;
+
+    write_metadata(&mut document, metadata);
+
+    let mut buffer = Vec::new();
+    document.save_to(&mut buffer).map_err(|e| {
+        SimplePdfGeneratorError::PdfError(format!("Cannot save the post-processed pdf: {}", e))
+    })?;
+
+    Ok(buffer)
+}
+
+fn write_metadata(document: &mut Document, metadata: &PdfMetadata) {
+    fn set_if(info: &mut Dictionary, key: &str, value: &Option<String>) {
+        if let Some(value) = value {
+            info.set(key, Object::string_literal(value.as_str()));
+        }
+    }
+
+    // Merge into the existing Info dictionary (if any) so the generator's own
+    // entries (CreationDate, Producer, …) aren't stripped.
+    let existing_info_id = document
+        .trailer
+        .get(b"Info")
+        .ok()
+        .and_then(|info| info.as_reference().ok());
+    let mut info = existing_info_id
+        .and_then(|id| document.get_object(id).ok())
+        .and_then(|object| object.as_dict().ok())
+        .cloned()
+        .unwrap_or_default();
+
+    set_if(&mut info, "Title", &metadata.title);
+    set_if(&mut info, "Author", &metadata.author);
+    set_if(&mut info, "Subject", &metadata.subject);
+    set_if(&mut info, "Keywords", &metadata.keywords);
+    set_if(&mut info, "Creator", &metadata.creator);
+
+    match existing_info_id {
+        Some(id) => {
+            document.objects.insert(id, Object::Dictionary(info));
+        }
+        None => {
+            let info_id = document.add_object(Object::Dictionary(info));
+            document.trailer.set("Info", info_id);
+        }
+    }
+}
+
+/// Waits for `condition` to hold, bounded by `overall_timeout`. A timeout is
+/// reported as a [`PdfError`](SimplePdfGeneratorError::PdfError) so late-rendered
+/// content (tables, external fonts, math) finishes before capture.
+async fn wait_for_ready(
+    page: &Page,
+    condition: &ReadyCondition,
+    overall_timeout: Duration,
+) -> Result<(), SimplePdfGeneratorError> {
+    let wait = async {
+        match condition {
+            ReadyCondition::NetworkIdle { idle_ms } => {
+                // Enable the Network domain first, otherwise no
+                // `EventRequestWillBeSent` is ever emitted and the idle loop
+                // would no-op and return ready immediately.
+                page.execute(NetworkEnableParams::default())
+                    .await
+                    .map_err(|e| {
+                        SimplePdfGeneratorError::BrowserError(format!(
+                            "Cannot enable the network domain: {}",
+                            e
+                        ))
+                    })?;
+
+                let mut requests = page
+                    .event_listener::<EventRequestWillBeSent>()
+                    .await
+                    .map_err(|e| {
+                        SimplePdfGeneratorError::BrowserError(format!(
+                            "Cannot subscribe to network events: {}",
+                            e
+                        ))
+                    })?;
+
+                // Each request resets the idle window; once a full `idle_ms`
+                // passes with no new request the page is considered idle. A
+                // closed stream (`Ok(None)`) means no further requests can
+                // arrive, so that is idle too.
+                while let Ok(Some(_)) =
+                    tokio::time::timeout(Duration::from_millis(*idle_ms), requests.next()).await
+                {}
+
+                Ok(())
+            }
+            ReadyCondition::Selector(selector) => {
+                let script = format!("document.querySelector(\"{}\") !== null", selector);
+                loop {
+                    if eval_bool(page, &script).await? {
+                        return Ok(());
+                    }
+                    tokio::time::sleep(READY_POLL_INTERVAL).await;
+                }
+            }
+            ReadyCondition::JsExpression(expression) => {
+                let script = format!("!!({})", expression);
+                loop {
+                    if eval_bool(page, &script).await? {
+                        return Ok(());
+                    }
+                    tokio::time::sleep(READY_POLL_INTERVAL).await;
+                }
+            }
+            ReadyCondition::Delay(duration) => {
+                tokio::time::sleep(*duration).await;
+                Ok(())
+            }
+        }
+    };
+
+    match tokio::time::timeout(overall_timeout, wait).await {
+        Ok(result) => result,
+        Err(_) => Err(SimplePdfGeneratorError::PdfError(
+            "Timed out waiting for the page to become ready".to_string(),
+        )),
+    }
+}
+
+async fn eval_bool(page: &Page, script: &str) -> Result<bool, SimplePdfGeneratorError> {
+    page.evaluate(script)
+        .await
+        .map_err(|e| {
+            SimplePdfGeneratorError::BrowserError(format!(
+                "Cannot evaluate the readiness condition: {}",
+                e
+            ))
+        })?
+        .into_value()
+        .map_err(|e| {
+            SimplePdfGeneratorError::BrowserError(format!(
+                "Cannot read the readiness condition result: {}",
+                e
+            ))
+        })
 }
 
 async fn inject_js(page: &Page, js: String) -> Result<EvaluationResult, CdpError> {
@@ -476,7 +1278,11 @@ async fn get_browser<'a>() -> tokio::sync::RwLockReadGuard<'a, Option<ChromiumIn
     let mut write_guard = BROWSER.write().await;
 
     if write_guard.is_none() {
-        *write_guard = Some(ChromiumInstance::new().await);
+        // Leave the slot empty on failure; callers surface the missing browser
+        // as a `BrowserError` so a failed launch can't panic the whole process.
+        if let Ok(instance) = ChromiumInstance::new().await {
+            *write_guard = Some(instance);
+        }
     }
 
     drop(write_guard);
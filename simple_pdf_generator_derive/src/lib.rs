@@ -1,34 +1,172 @@
 use proc_macro::{self, TokenStream};
 use quote::quote;
-use syn::{parse_macro_input, Data, DeriveInput};
+use syn::{parse_macro_input, Attribute, Data, DeriveInput};
 
-#[proc_macro_derive(PdfTemplate, attributes(PdfTableData))]
-pub fn pdf_template_property(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
-    let struct_name = &input.ident;
+/// Case transform applied to every inserted key by the container-level
+/// `#[pdf(rename_all = "...")]` attribute, mirroring serde_derive.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Camel,
+    Kebab,
+    Pascal,
+    Snake,
+}
 
-    let struct_fields = match input.data {
-        Data::Struct(ref data) => &data.fields,
-        _ => panic!("PdfTemplate can only be derived for structs"),
-    };
+impl RenameRule {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "camelCase" => Some(RenameRule::Camel),
+            "kebab-case" => Some(RenameRule::Kebab),
+            "PascalCase" => Some(RenameRule::Pascal),
+            "snake_case" => Some(RenameRule::Snake),
+            _ => None,
+        }
+    }
 
-    let inspect_struct_fields = struct_fields.iter().map(|field| {
-        let field_name = &field.ident;
-        let field_ty = &field.ty;
+    /// Applies the transform to a `snake_case` Rust field name.
+    fn apply(self, name: &str) -> String {
+        match self {
+            RenameRule::Snake => name.to_string(),
+            RenameRule::Kebab => name.replace('_', "-"),
+            RenameRule::Camel | RenameRule::Pascal => {
+                let mut out = String::new();
+                for (index, part) in name.split('_').filter(|p| !p.is_empty()).enumerate() {
+                    if index == 0 && matches!(self, RenameRule::Camel) {
+                        out.push_str(part);
+                    } else {
+                        let mut chars = part.chars();
+                        if let Some(first) = chars.next() {
+                            out.extend(first.to_uppercase());
+                            out.push_str(chars.as_str());
+                        }
+                    }
+                }
+                out
+            }
+        }
+    }
+}
 
-        let is_tabledata = field
-            .attrs
-            .iter()
-            .any(|attr| attr.path().is_ident("PdfTableData"));
+/// Options parsed from a container-level `#[pdf(...)]` attribute.
+#[derive(Default)]
+struct ContainerOpts {
+    rename_all: Option<RenameRule>,
+    schema: Option<String>,
+}
 
-        if is_tabledata {
-            quote! {
-                template.tables.insert(
-                    stringify!(#field_name).to_string(),
-                    stringify_object(&self.#field_name),
-                );
+/// Options parsed from a field-level `#[pdf(...)]` attribute.
+#[derive(Default)]
+struct FieldOpts {
+    rename: Option<String>,
+    skip: bool,
+    format: Option<String>,
+    date_format: Option<String>,
+}
+
+fn parse_container_opts(attrs: &[Attribute]) -> ContainerOpts {
+    let mut opts = ContainerOpts::default();
+    for attr in attrs {
+        if !attr.path().is_ident("pdf") {
+            continue;
+        }
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                opts.rename_all = RenameRule::parse(&value.value());
+            } else if meta.path.is_ident("schema") {
+                opts.schema = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            }
+            Ok(())
+        });
+    }
+    opts
+}
+
+fn parse_field_opts(attrs: &[Attribute]) -> FieldOpts {
+    let mut opts = FieldOpts::default();
+    for attr in attrs {
+        if !attr.path().is_ident("pdf") {
+            continue;
+        }
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                opts.skip = true;
+            } else if meta.path.is_ident("rename") {
+                opts.rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            } else if meta.path.is_ident("format") {
+                opts.format = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            } else if meta.path.is_ident("date_format") {
+                opts.date_format = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            }
+            Ok(())
+        });
+    }
+    opts
+}
+
+/// Resolves the key a field is inserted under, honouring `rename` (which wins)
+/// and the container's `rename_all` transform.
+fn resolved_key(field_name: &str, field_opts: &FieldOpts, container: &ContainerOpts) -> String {
+    if let Some(rename) = &field_opts.rename {
+        return rename.clone();
+    }
+    match container.rename_all {
+        Some(rule) => rule.apply(field_name),
+        None => field_name.to_string(),
+    }
+}
+
+/// Builds the per-field `template.properties`/`template.tables` inserts shared
+/// by both derives.
+fn build_inserts(
+    struct_fields: &syn::Fields,
+    container: &ContainerOpts,
+) -> Vec<proc_macro2::TokenStream> {
+    struct_fields
+        .iter()
+        .filter_map(|field| {
+            let field_name = &field.ident;
+            let field_ty = &field.ty;
+
+            let field_opts = parse_field_opts(&field.attrs);
+            if field_opts.skip {
+                return None;
             }
-        } else {
+
+            let base_key = field_name.as_ref().unwrap().to_string();
+            let key = resolved_key(&base_key, &field_opts, container);
+
+            let is_tabledata = field
+                .attrs
+                .iter()
+                .any(|attr| attr.path().is_ident("PdfTableData"));
+
+            if is_tabledata {
+                return Some(quote! {
+                    template.tables.insert(
+                        #key.to_string(),
+                        stringify_object(&self.#field_name),
+                    );
+                });
+            }
+
+            // Branches that render the field value, wrapping it in a
+            // `format!`/chrono call when the corresponding option is set.
+            let some_branch = if let Some(fmt) = &field_opts.format {
+                quote! { format!(#fmt, value) }
+            } else if let Some(date_format) = &field_opts.date_format {
+                quote! { value.format(#date_format).to_string() }
+            } else {
+                quote! { value.to_string() }
+            };
+            let plain_branch = if let Some(fmt) = &field_opts.format {
+                quote! { format!(#fmt, self.#field_name) }
+            } else if let Some(date_format) = &field_opts.date_format {
+                quote! { self.#field_name.format(#date_format).to_string() }
+            } else {
+                quote! { self.#field_name.to_string() }
+            };
+
             let property = match field_ty {
                 syn::Type::Path(type_path) => {
                     let type_name = type_path.path.segments.first().unwrap().ident.to_string();
@@ -36,7 +174,7 @@ pub fn pdf_template_property(input: TokenStream) -> TokenStream {
                         quote! {
                             simple_pdf_generator::Property {
                                 val: match &self.#field_name {
-                                    std::option::Option::Some(value) => value.to_string(),
+                                    std::option::Option::Some(value) => #some_branch,
                                     std::option::Option::None => String::new(),
                                 },
                                 is_none: self.#field_name.is_none(),
@@ -46,7 +184,7 @@ pub fn pdf_template_property(input: TokenStream) -> TokenStream {
                     } else {
                         quote! {
                             simple_pdf_generator::Property {
-                                val: self.#field_name.to_string(),
+                                val: #plain_branch,
                                 is_none: false,
                                 is_tabledata: false,
                             }
@@ -55,22 +193,179 @@ pub fn pdf_template_property(input: TokenStream) -> TokenStream {
                 }
                 _ => quote! {
                     simple_pdf_generator::Property {
-                        val: self.#field_name.to_string(),
+                        val: #plain_branch,
                         is_none: false,
                         is_tabledata: false,
                     }
                 },
             };
 
-            quote! {
+            Some(quote! {
                 template.properties.insert(
-                    stringify!(#field_name).to_string(),
+                    #key.to_string(),
                     #property,
                 );
+            })
+        })
+        .collect()
+}
+
+/// Infers the schema type a field maps to: `table` for `#[PdfTableData]`
+/// fields, otherwise the scalar type derived from the Rust type.
+fn infer_schema_type(ty: &syn::Type, is_tabledata: bool) -> &'static str {
+    if is_tabledata {
+        "table"
+    } else {
+        scalar_type_name(ty)
+    }
+}
+
+fn scalar_type_name(ty: &syn::Type) -> &'static str {
+    if let syn::Type::Path(type_path) = ty {
+        let segment = type_path.path.segments.last().unwrap();
+        let name = segment.ident.to_string();
+        if name == "Option" {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                    return scalar_type_name(inner);
+                }
             }
+            return "string";
         }
+        match name.as_str() {
+            "bool" => "bool",
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64"
+            | "u128" | "usize" | "f32" | "f64" => "number",
+            _ => "string",
+        }
+    } else {
+        "string"
+    }
+}
+
+/// Loads the schema descriptor, a flat JSON object mapping each allowed
+/// property name to its expected type (`string`/`number`/`bool`/`table`).
+/// Paths are resolved relative to the consuming crate's manifest directory.
+fn load_schema(path: &str) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let manifest_dir =
+        std::env::var("CARGO_MANIFEST_DIR").map_err(|_| "CARGO_MANIFEST_DIR is not set".to_string())?;
+    let full_path = std::path::Path::new(&manifest_dir).join(path);
+    let contents = std::fs::read_to_string(&full_path)
+        .map_err(|e| format!("cannot read schema `{}`: {}", full_path.display(), e))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("cannot parse schema `{}`: {}", full_path.display(), e))
+}
+
+/// Builds the schema validation for a derive: compile-time `compile_error!`s for
+/// fields that are undeclared or have the wrong type, plus a runtime check that
+/// every required schema key ends up in the built template.
+fn build_schema_validation(
+    struct_fields: &syn::Fields,
+    container: &ContainerOpts,
+) -> (Vec<proc_macro2::TokenStream>, proc_macro2::TokenStream) {
+    let schema_path = match &container.schema {
+        Some(path) => path,
+        None => return (Vec::new(), quote! {}),
+    };
+
+    let schema = match load_schema(schema_path) {
+        Ok(schema) => schema,
+        Err(message) => {
+            let error =
+                syn::Error::new(proc_macro2::Span::call_site(), message).to_compile_error();
+            return (vec![error], quote! {});
+        }
+    };
+
+    let mut errors = Vec::new();
+    for field in struct_fields.iter() {
+        let field_opts = parse_field_opts(&field.attrs);
+        if field_opts.skip {
+            continue;
+        }
+
+        let base_key = field.ident.as_ref().unwrap().to_string();
+        let key = resolved_key(&base_key, &field_opts, container);
+        let is_tabledata = field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("PdfTableData"));
+        let inferred = infer_schema_type(&field.ty, is_tabledata);
+
+        match schema.get(&key) {
+            Some(expected) if expected.as_str() == inferred => {}
+            Some(expected) => errors.push(
+                syn::Error::new_spanned(
+                    field,
+                    format!(
+                        "field `{}` maps to schema type `{}` but the schema declares `{}`",
+                        key, inferred, expected
+                    ),
+                )
+                .to_compile_error(),
+            ),
+            None => errors.push(
+                syn::Error::new_spanned(
+                    field,
+                    format!("field `{}` is not declared in the schema", key),
+                )
+                .to_compile_error(),
+            ),
+        }
+    }
+
+    let entries = schema.iter().map(|(key, ty)| {
+        let kind = if ty == "table" { "table" } else { "property" };
+        quote! { (#key, #kind) }
     });
 
+    let runtime_check = quote! {
+        const __PDF_SCHEMA: &[(&str, &str)] = &[#(#entries),*];
+        for (key, kind) in __PDF_SCHEMA {
+            let present = if *kind == "table" {
+                template.tables.contains_key(*key)
+            } else {
+                template.properties.contains_key(*key)
+            };
+            if !present {
+                return std::result::Result::Err(
+                    simple_pdf_generator::SimplePdfGeneratorError::SchemaMismatch(
+                        format!("missing required schema key `{}`", key),
+                    ),
+                );
+            }
+        }
+    };
+
+    (errors, runtime_check)
+}
+
+fn stringify_object_impl() -> proc_macro2::TokenStream {
+    quote! {
+        // Emit well-formed, arbitrarily nested JSON for table data. Delegating
+        // to `serde_json` recurses over the whole value tree, so nested structs,
+        // sub-lists and maps inside a row serialize correctly instead of the old
+        // single-depth `{key:value,...}` hybrid.
+        fn stringify_object<T: serde::Serialize>(obj: &T) -> String {
+            serde_json::to_string(obj).unwrap_or_else(|_| "null".to_string())
+        }
+    }
+}
+
+#[proc_macro_derive(PdfTemplate, attributes(PdfTableData, pdf))]
+pub fn pdf_template_property(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let struct_name = &input.ident;
+
+    let struct_fields = match input.data {
+        Data::Struct(ref data) => &data.fields,
+        _ => panic!("PdfTemplate can only be derived for structs"),
+    };
+
+    let container_opts = parse_container_opts(&input.attrs);
+    let inspect_struct_fields = build_inserts(struct_fields, &container_opts);
+    let (schema_errors, schema_check) = build_schema_validation(struct_fields, &container_opts);
+
     let impl_methods = quote! {
         impl #struct_name {
             pub async fn generate_pdf(&self,
@@ -82,42 +377,24 @@ pub fn pdf_template_property(input: TokenStream) -> TokenStream {
                 template.html_path = html_path;
                 #(#inspect_struct_fields)*
 
+                #schema_check
+
                 simple_pdf_generator::generate_pdf(template, assets, print_options).await
             }
         }
     };
 
-    let utility_methods = quote! {
-        fn stringify_object<T: serde::Serialize>(obj: &T) -> String {
-            let mut result = String::new();
-
-            let serialized = serde_json::to_value(obj).unwrap();
-            if let serde_json::Value::Object(map) = &serialized {
-                result.push('{');
-                for (key, value) in map {
-                    result.push_str(&format!("{}:{},", key, value));
-                }
-                result.push('}');
-            } else if let serde_json::Value::Array(array) = serialized {
-                result.push('[');
-                for value in array {
-                    result.push_str(&format!("{},", value));
-                }
-                result.push(']');
-            }
-
-            result
-        }
-    };
+    let utility_methods = stringify_object_impl();
 
     quote! {
+        #(#schema_errors)*
         #impl_methods
         #utility_methods
     }
     .into()
 }
 
-#[proc_macro_derive(PdfTemplateForHtml, attributes(PdfTableData))]
+#[proc_macro_derive(PdfTemplateForHtml, attributes(PdfTableData, pdf))]
 pub fn pdf_template_property_for_html_string(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     let struct_name = &input.ident;
@@ -127,105 +404,32 @@ pub fn pdf_template_property_for_html_string(input: TokenStream) -> TokenStream
         _ => panic!("PdfTemplateForHtml can only be derived for structs"),
     };
 
-    let inspect_struct_fields = struct_fields.iter().map(|field| {
-        let field_name = &field.ident;
-        let field_ty = &field.ty;
-
-        let is_tabledata = field
-            .attrs
-            .iter()
-            .any(|attr| attr.path().is_ident("PdfTableData"));
-
-        if is_tabledata {
-            quote! {
-                template.tables.insert(
-                    stringify!(#field_name).to_string(),
-                    stringify_object(&self.#field_name),
-                );
-            }
-        } else {
-            let property = match field_ty {
-                syn::Type::Path(type_path) => {
-                    let type_name = type_path.path.segments.first().unwrap().ident.to_string();
-                    if type_name == "Option" {
-                        quote! {
-                            simple_pdf_generator::Property {
-                                val: match &self.#field_name {
-                                    std::option::Option::Some(value) => value.to_string(),
-                                    std::option::Option::None => String::new(),
-                                },
-                                is_none: self.#field_name.is_none(),
-                                is_tabledata: false,
-                            }
-                        }
-                    } else {
-                        quote! {
-                            simple_pdf_generator::Property {
-                                val: self.#field_name.to_string(),
-                                is_none: false,
-                                is_tabledata: false,
-                            }
-                        }
-                    }
-                }
-                _ => quote! {
-                    simple_pdf_generator::Property {
-                        val: self.#field_name.to_string(),
-                        is_none: false,
-                        is_tabledata: false,
-                    }
-                },
-            };
-
-            quote! {
-                template.properties.insert(
-                    stringify!(#field_name).to_string(),
-                    #property,
-                );
-            }
-        }
-    });
+    let container_opts = parse_container_opts(&input.attrs);
+    let inspect_struct_fields = build_inserts(struct_fields, &container_opts);
+    let (schema_errors, schema_check) = build_schema_validation(struct_fields, &container_opts);
 
     let impl_methods = quote! {
         impl #struct_name {
             pub async fn generate_pdf_from_html(&self,
               html_string: String,
-              attributes: 
               assets: &[simple_pdf_generator::Asset],
               print_options: &simple_pdf_generator::PrintOptions,
           ) -> std::result::Result<Vec<u8>, simple_pdf_generator::SimplePdfGeneratorError> {
               let mut template = simple_pdf_generator::Template::default();
 
               #(#inspect_struct_fields)*
+
+              #schema_check
+
               simple_pdf_generator::generate_pdf_from_html(html_string, template, assets, print_options).await
           }
         }
     };
 
-    let utility_methods = quote! {
-        fn stringify_object<T: serde::Serialize>(obj: &T) -> String {
-            let mut result = String::new();
-
-            let serialized = serde_json::to_value(obj).unwrap();
-            if let serde_json::Value::Object(map) = &serialized {
-                result.push('{');
-                for (key, value) in map {
-                    result.push_str(&format!("{}:{},", key, value));
-                }
-                result.push('}');
-            } else if let serde_json::Value::Array(array) = serialized {
-                result.push('[');
-                for value in array {
-                    result.push_str(&format!("{},", value));
-                }
-                result.push(']');
-            }
-
-            result
-        }
-    };
+    let utility_methods = stringify_object_impl();
 
     quote! {
+        #(#schema_errors)*
         #impl_methods
         #utility_methods
     }
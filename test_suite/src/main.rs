@@ -2,7 +2,7 @@ use std::{env, time};
 
 use futures::future::join_all;
 use serde::Serialize;
-use simple_pdf_generator::{Asset, AssetType, PrintOptions};
+use simple_pdf_generator::{Asset, AssetType, Length, PaperSize, PrintOptions};
 use simple_pdf_generator_derive::PdfTemplate;
 
 #[derive(PdfTemplate)]
@@ -83,12 +83,11 @@ async fn main() {
     }];
 
     let print_options = PrintOptions {
-        paper_width: Some(210.0),
-        paper_height: Some(297.0),
-        margin_top: Some(10.0),
-        margin_bottom: Some(10.0),
-        margin_left: Some(10.0),
-        margin_right: Some(10.0),
+        paper_size: Some(PaperSize::A4),
+        margin_top: Some(Length::mm(10.0)),
+        margin_bottom: Some(Length::mm(10.0)),
+        margin_left: Some(Length::mm(10.0)),
+        margin_right: Some(Length::mm(10.0)),
         ..PrintOptions::default()
     };
 